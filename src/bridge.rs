@@ -0,0 +1,155 @@
+use crate::config::BridgeConfig;
+use crate::Event;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use twitch_irc::message::PrivmsgMessage;
+
+/// Maximum length, in bytes, of a line on the wire per RFC 1459.
+const IRC_LINE_LIMIT: usize = 512;
+
+trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AsyncStream for T {}
+
+/// Handle to the relay task. Forwarding is fire-and-forget: a dropped or
+/// never-connected bridge just silently swallows messages instead of
+/// taking the TUI down with it.
+pub struct Bridge {
+    outgoing: Option<mpsc::UnboundedSender<PrivmsgMessage>>,
+}
+
+impl Bridge {
+    /// Does nothing but hold an empty sender; used when `[bridge]` is
+    /// disabled in config.
+    pub fn disabled() -> Self {
+        Bridge { outgoing: None }
+    }
+
+    /// Spawns the relay task: connects, registers, then forwards whatever
+    /// comes in over the returned channel. Connection/registration
+    /// failures are reported as a single `Event::BridgeStatus` and the
+    /// task exits; they never reach the render loop as an error.
+    pub fn start(config: BridgeConfig, events: mpsc::UnboundedSender<Event>) -> Self {
+        if !config.enabled {
+            return Bridge::disabled();
+        }
+
+        let (outgoing, mut incoming) = mpsc::unbounded_channel::<PrivmsgMessage>();
+        tokio::spawn(async move {
+            let mut stream = match connect(&config).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = events.send(Event::BridgeStatus(format!("bridge: failed to connect: {}", e)));
+                    return;
+                }
+            };
+            if let Err(e) = register(&mut stream, &config).await {
+                let _ = events.send(Event::BridgeStatus(format!("bridge: registration failed: {}", e)));
+                return;
+            }
+            let _ = events.send(Event::BridgeStatus(format!(
+                "bridge: connected to {}:{} as {}",
+                config.server, config.port, config.nick
+            )));
+
+            // The server expects a PONG for every PING or it drops the
+            // connection as dead within a minute or two, so the read side
+            // has to stay serviced even though we never care about other
+            // replies (best-effort relay, not a full client).
+            let (reader, mut writer) = tokio::io::split(stream);
+            let mut lines = BufReader::new(reader).lines();
+            loop {
+                tokio::select! {
+                    line = lines.next_line() => {
+                        match line {
+                            Ok(Some(line)) => {
+                                if let Some(rest) = line.strip_prefix("PING") {
+                                    if let Err(e) = writer.write_all(format!("PONG{}\r\n", rest).as_bytes()).await {
+                                        let _ = events.send(Event::BridgeStatus(format!("bridge: pong failed: {}", e)));
+                                        break;
+                                    }
+                                }
+                            }
+                            Ok(None) => {
+                                let _ = events.send(Event::BridgeStatus("bridge: server closed the connection".to_owned()));
+                                break;
+                            }
+                            Err(e) => {
+                                let _ = events.send(Event::BridgeStatus(format!("bridge: read failed: {}", e)));
+                                break;
+                            }
+                        }
+                    }
+                    privmsg = incoming.recv() => {
+                        match privmsg {
+                            Some(privmsg) => {
+                                let line = format_line(&config.channel, &privmsg);
+                                if let Err(e) = writer.write_all(line.as_bytes()).await {
+                                    let _ = events.send(Event::BridgeStatus(format!("bridge: forward failed: {}", e)));
+                                    break;
+                                }
+                            }
+                            None => break,
+                        }
+                    }
+                }
+            }
+        });
+
+        Bridge { outgoing: Some(outgoing) }
+    }
+
+    /// Queues `privmsg` for relaying. A send failure (e.g. the relay task
+    /// already exited) is dropped silently rather than surfaced.
+    pub fn forward(&self, privmsg: &PrivmsgMessage) {
+        if let Some(outgoing) = &self.outgoing {
+            let _ = outgoing.send(privmsg.clone());
+        }
+    }
+}
+
+async fn connect(config: &BridgeConfig) -> anyhow::Result<Box<dyn AsyncStream>> {
+    let tcp = TcpStream::connect((config.server.as_str(), config.port)).await?;
+    if !config.tls {
+        return Ok(Box::new(tcp));
+    }
+    let connector = tokio_native_tls::TlsConnector::from(native_tls::TlsConnector::new()?);
+    let tls = connector.connect(&config.server, tcp).await?;
+    Ok(Box::new(tls))
+}
+
+async fn register(stream: &mut (dyn AsyncStream + '_), config: &BridgeConfig) -> anyhow::Result<()> {
+    stream
+        .write_all(format!("NICK {}\r\n", config.nick).as_bytes())
+        .await?;
+    stream
+        .write_all(format!("USER {} 0 * :{}\r\n", config.nick, config.nick).as_bytes())
+        .await?;
+    stream
+        .write_all(format!("JOIN {}\r\n", config.channel).as_bytes())
+        .await?;
+    Ok(())
+}
+
+/// Formats `privmsg` as a `PRIVMSG <channel> :<sender>: <text>` line,
+/// truncated (on a char boundary) to the IRC line length limit.
+fn format_line(channel: &str, privmsg: &PrivmsgMessage) -> String {
+    let body = format!(
+        "PRIVMSG {} :{}: {}",
+        channel, privmsg.sender.name, privmsg.message_text
+    );
+    let mut line = truncate_to_bytes(&body, IRC_LINE_LIMIT - 2);
+    line.push_str("\r\n");
+    line
+}
+
+fn truncate_to_bytes(s: &str, max_bytes: usize) -> String {
+    if s.len() <= max_bytes {
+        return s.to_owned();
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].to_owned()
+}
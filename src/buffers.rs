@@ -0,0 +1,129 @@
+use crate::chat::{push_chat_line, ChatLine};
+use crate::config::Config;
+use crate::history::History;
+use std::collections::{HashMap, VecDeque};
+
+/// Normalizes a channel name the way Twitch IRC does: lowercase, no
+/// leading `#`. Incoming `Privmsg`s are always keyed by
+/// `channel_login`, which is already in this form; config entries and
+/// `/join` arguments are user-typed and aren't, so every path that turns
+/// a channel name into a buffer key must normalize through this first or
+/// the same channel ends up split across two buffers.
+pub fn normalize_channel_name(channel: &str) -> String {
+    channel.trim_start_matches('#').to_lowercase()
+}
+
+/// One scrollback per joined channel, plus which one is on screen.
+///
+/// Only the active buffer's `History` is kept in sync on every push;
+/// inactive buffers just accumulate an unread count so switching to them
+/// is cheap and doesn't require recomputing scroll state for channels
+/// nobody is looking at.
+pub struct Buffers {
+    names: Vec<String>,
+    logs: HashMap<String, VecDeque<ChatLine>>,
+    unread: HashMap<String, usize>,
+    active: usize,
+}
+
+impl Buffers {
+    pub fn new(channels: &[String]) -> Self {
+        let mut buffers = Buffers {
+            names: Vec::new(),
+            logs: HashMap::new(),
+            unread: HashMap::new(),
+            active: 0,
+        };
+        for channel in channels {
+            buffers.ensure_channel(channel);
+        }
+        buffers
+    }
+
+    fn ensure_channel(&mut self, channel: &str) {
+        let channel = normalize_channel_name(channel);
+        if !self.logs.contains_key(&channel) {
+            self.names.push(channel.clone());
+            self.logs.insert(channel.clone(), VecDeque::new());
+            self.unread.insert(channel, 0);
+        }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_channel(&self) -> &str {
+        &self.names[self.active]
+    }
+
+    pub fn active_messages(&self) -> &VecDeque<ChatLine> {
+        &self.logs[self.active_channel()]
+    }
+
+    pub fn unread(&self, channel: &str) -> usize {
+        *self.unread.get(channel).unwrap_or(&0)
+    }
+
+    /// Switches to `channel`, creating a buffer for it first if this is
+    /// the first time we've seen it (e.g. a channel joined via `/join`).
+    pub fn switch_to(&mut self, channel: &str) {
+        let channel = normalize_channel_name(channel);
+        self.ensure_channel(&channel);
+        self.active = self
+            .names
+            .iter()
+            .position(|c| c == &channel)
+            .expect("just ensured");
+        self.unread.insert(channel, 0);
+    }
+
+    pub fn next(&mut self) {
+        self.active = (self.active + 1) % self.names.len();
+        self.unread.insert(self.active_channel().to_owned(), 0);
+    }
+
+    pub fn prev(&mut self) {
+        self.active = (self.active + self.names.len() - 1) % self.names.len();
+        self.unread.insert(self.active_channel().to_owned(), 0);
+    }
+
+    /// Clears the active buffer's scrollback.
+    pub fn clear_active(&mut self, history: &mut History) {
+        let channel = self.active_channel().to_owned();
+        self.logs.get_mut(&channel).expect("active channel always has a buffer").clear();
+        history.recompute(history.width(), history.height(), std::iter::empty());
+    }
+
+    /// Pushes a line the user generated locally (echoed messages, command
+    /// output, errors) into the active buffer.
+    pub fn push_active(&mut self, history: &mut History, config: &Config, line: ChatLine) {
+        let channel = self.active_channel().to_owned();
+        let log = self
+            .logs
+            .get_mut(&channel)
+            .expect("active channel always has a buffer");
+        push_chat_line(log, history, config, line);
+    }
+
+    /// Routes an incoming message into the buffer for `channel`, only
+    /// touching scroll state if that buffer is the one on screen.
+    pub fn push_incoming(&mut self, channel: &str, history: &mut History, config: &Config, line: ChatLine) {
+        let channel = normalize_channel_name(channel);
+        self.ensure_channel(&channel);
+        if channel == self.active_channel() {
+            self.push_active(history, config, line);
+            return;
+        }
+        let log = self.logs.get_mut(&channel).expect("just ensured");
+        log.push_front(line);
+        if log.len() > config.messages_buffer_size {
+            log.pop_back();
+        }
+        *self.unread.entry(channel).or_insert(0) += 1;
+    }
+}
@@ -0,0 +1,168 @@
+use crate::config::Config;
+use crate::filters::Filters;
+use crate::history::History;
+use std::collections::VecDeque;
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::ListItem;
+use twitch_irc::message::{PrivmsgMessage, RGBColor};
+use unicode_width::UnicodeWidthStr;
+
+/// A line in the chat log: either a message received from Twitch, or one
+/// produced locally (an echoed outgoing message, or a status/error line).
+#[derive(Debug)]
+pub enum ChatLine {
+    Privmsg(Box<PrivmsgMessage>),
+    Local { sender: String, text: String },
+}
+
+fn luminance(color: &RGBColor) -> f32 {
+    0.2126 * (color.r as f32) + 0.7152 * (color.g as f32) + 0.00722 * (color.b as f32)
+}
+
+fn style_for(config: &Config, m: &PrivmsgMessage) -> Style {
+    match &m.name_color {
+        Some(color) => {
+            let style = Style::default().fg(Color::Rgb(color.r, color.g, color.b));
+            if luminance(color) < config.invert_below_brightness as f32 {
+                style.bg(Color::Gray)
+            } else {
+                style
+            }
+        }
+        None => Style::default(),
+    }
+}
+
+/// Builds the badge prefix for a message and the on-screen width taken up
+/// by the badges plus the sender's name and the trailing ": ".
+fn badge_prefix(config: &Config, m: &PrivmsgMessage) -> (String, usize) {
+    let is_subscriber = m.badges.iter().any(|b| b.name == "subscriber");
+    let is_founder = m.badges.iter().any(|b| b.name == "founder");
+    let is_mod = m.badges.iter().any(|b| b.name == "moderator");
+    let is_vip = m.badges.iter().any(|b| b.name == "vip");
+
+    let mut width_for_name: usize = UnicodeWidthStr::width(m.sender.name.as_str()) + 2 /* ": " */;
+    let mut badges = String::new();
+    if is_subscriber {
+        badges.push_str(&config.subscriber_symbol);
+        width_for_name += config.subscriber_symbol_width;
+    }
+    if is_founder {
+        badges.push_str(&config.founder_symbol);
+        width_for_name += config.founder_symbol_width;
+    }
+    if is_mod {
+        badges.push_str(&config.mod_symbol);
+        width_for_name += config.mod_symbol_width;
+    }
+    if is_vip {
+        badges.push_str(&config.vip_symbol);
+        width_for_name += config.vip_symbol_width;
+    }
+    (badges, width_for_name)
+}
+
+fn chat_line_text(line: &ChatLine) -> &str {
+    match line {
+        ChatLine::Privmsg(m) => &m.message_text,
+        ChatLine::Local { text, .. } => text,
+    }
+}
+
+fn chat_line_sender(line: &ChatLine) -> &str {
+    match line {
+        ChatLine::Privmsg(m) => &m.sender.name,
+        ChatLine::Local { sender, .. } => sender,
+    }
+}
+
+fn chat_line_style(config: &Config, line: &ChatLine) -> Style {
+    match line {
+        ChatLine::Privmsg(m) => style_for(config, m),
+        ChatLine::Local { .. } => Style::default(),
+    }
+}
+
+/// Badge prefix and the on-screen width taken up by badges plus the
+/// sender's name and the trailing ": ". Local lines carry no badges.
+fn chat_line_prefix(config: &Config, line: &ChatLine) -> (String, usize) {
+    match line {
+        ChatLine::Privmsg(m) => badge_prefix(config, m),
+        ChatLine::Local { sender, .. } => (String::new(), UnicodeWidthStr::width(sender.as_str()) + 2),
+    }
+}
+
+/// Number of wrapped `ListItem`s `render_chat_line` would produce for this
+/// line at `area_width`. Wraps the text the same way `render_chat_line`
+/// does so scrollback bookkeeping (`History`) agrees with what's actually
+/// drawn, rather than estimating from byte length.
+pub fn chat_line_wrapped_line_count(config: &Config, line: &ChatLine, area_width: usize) -> usize {
+    let (_, width_for_name) = chat_line_prefix(config, line);
+    let width_for_text = area_width.saturating_sub(width_for_name).max(1);
+    textwrap::fill(chat_line_text(line), width_for_text)
+        .split('\n')
+        .count()
+}
+
+/// Renders a single chat line as one or more wrapped `ListItem`s, in
+/// top-to-bottom order: the first line carries the badges and sender
+/// name, continuation lines are indented to align under the text. Lines
+/// matching a highlight rule get that rule's background applied across
+/// the whole line (badges, name, and message text, including
+/// continuation lines), not just the sender's name, so a mention
+/// anywhere in the line stands out.
+pub fn render_chat_line(
+    config: &Config,
+    filters: &Filters,
+    line: &ChatLine,
+    area_width: usize,
+) -> Vec<ListItem<'static>> {
+    let name_style = chat_line_style(config, line);
+    let highlight = filters.highlight_style(chat_line_sender(line), chat_line_text(line));
+    let line_style = highlight.unwrap_or_default();
+    let name_style = match highlight {
+        Some(highlight) => name_style.patch(highlight),
+        None => name_style,
+    };
+    let (badges, width_for_name) = chat_line_prefix(config, line);
+    let width_for_text = area_width.saturating_sub(width_for_name).max(1);
+    let wrapped = textwrap::fill(chat_line_text(line), width_for_text);
+    let mut lines = wrapped.split('\n');
+
+    let mut items = Vec::new();
+    let first = lines.next().expect("message came with no first line");
+    items.push(ListItem::new(Spans(vec![
+        Span::styled(badges, line_style),
+        Span::styled(chat_line_sender(line).to_owned(), name_style),
+        Span::styled(": ", line_style),
+        Span::styled(first.to_owned(), line_style),
+    ])));
+    for l in lines {
+        items.push(ListItem::new(Spans(vec![
+            Span::styled((0..width_for_name).map(|_| " ").collect::<String>(), line_style),
+            Span::styled(l.to_owned(), line_style),
+        ])));
+    }
+    items
+}
+
+/// Pushes a line onto the scrollback, trimming the oldest line once over
+/// `messages_buffer_size` and recomputing the scroll `History`.
+pub fn push_chat_line(
+    messages: &mut VecDeque<ChatLine>,
+    history: &mut History,
+    config: &Config,
+    line: ChatLine,
+) {
+    messages.push_front(line);
+    if messages.len() > config.messages_buffer_size {
+        messages.pop_back();
+    }
+    let width = history.width();
+    history.recompute(
+        width,
+        history.height(),
+        messages.iter().rev().map(|l| chat_line_wrapped_line_count(config, l, width)),
+    );
+}
@@ -0,0 +1,174 @@
+use crate::buffers::{normalize_channel_name, Buffers};
+use crate::chat::ChatLine;
+use crate::config::Config;
+use crate::filters::Filters;
+use crate::history::History;
+use twitch_irc::login::StaticLoginCredentials;
+use twitch_irc::{TCPTransport, TwitchIRCClient};
+
+/// A parsed slash command, as typed into the input box.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Me(String),
+    Clear,
+    Join(String),
+    Ban(String),
+    Timeout(String, u64),
+    Mod(String),
+    FilterAdd(String),
+    /// Anything starting with `/` that isn't one of the above.
+    Unknown(String),
+}
+
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+/// Parses a slash command out of `input`, which must already be known to
+/// start with `/`.
+pub fn parse(input: &str) -> Command {
+    let body = input.strip_prefix('/').unwrap_or(input);
+    let mut parts = body.split_whitespace();
+    let name = parts.next().unwrap_or("");
+    match name {
+        "me" => Command::Me(parts.collect::<Vec<_>>().join(" ")),
+        "clear" => Command::Clear,
+        "join" => Command::Join(parts.next().unwrap_or("").to_owned()),
+        "ban" => Command::Ban(parts.next().unwrap_or("").to_owned()),
+        "timeout" => {
+            let user = parts.next().unwrap_or("").to_owned();
+            let secs = parts
+                .next()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_TIMEOUT_SECS);
+            Command::Timeout(user, secs)
+        }
+        "mod" => Command::Mod(parts.next().unwrap_or("").to_owned()),
+        "filter" => match parts.next() {
+            Some("add") => Command::FilterAdd(parts.collect::<Vec<_>>().join(" ")),
+            _ => Command::Unknown("filter".to_owned()),
+        },
+        other => Command::Unknown(other.to_owned()),
+    }
+}
+
+fn error_line(buffers: &mut Buffers, history: &mut History, config: &Config, text: String) {
+    buffers.push_active(
+        history,
+        config,
+        ChatLine::Local {
+            sender: "error".to_owned(),
+            text,
+        },
+    );
+}
+
+/// Sends a `.<command>` moderation line via chat, surfacing any failure
+/// as an inline error rather than propagating it.
+async fn send_mod_command(
+    client: &TwitchIRCClient<TCPTransport, StaticLoginCredentials>,
+    buffers: &mut Buffers,
+    history: &mut History,
+    config: &Config,
+    line: String,
+) {
+    if let Err(e) = client.say(buffers.active_channel().to_owned(), line).await {
+        error_line(buffers, history, config, format!("failed to send command: {}", e));
+    }
+}
+
+/// Runs a parsed command, sending chat/moderation messages via `client`
+/// and updating local state (the active buffer, the scrollback) as
+/// needed. Unknown commands surface an inline error instead of being
+/// sent to chat.
+pub async fn dispatch(
+    client: &TwitchIRCClient<TCPTransport, StaticLoginCredentials>,
+    buffers: &mut Buffers,
+    history: &mut History,
+    filters: &mut Filters,
+    config: &Config,
+    command: Command,
+) {
+    match command {
+        Command::Me(text) => {
+            let action = format!("\u{1}ACTION {}\u{1}", text);
+            if let Err(e) = client.say(buffers.active_channel().to_owned(), action).await {
+                error_line(buffers, history, config, format!("failed to send action: {}", e));
+            } else {
+                let sender = config.username.clone().unwrap_or_else(|| "you".to_owned());
+                buffers.push_active(
+                    history,
+                    config,
+                    ChatLine::Local {
+                        sender: "*".to_owned(),
+                        text: format!("{} {}", sender, text),
+                    },
+                );
+            }
+        }
+        Command::Clear => buffers.clear_active(history),
+        Command::Join(channel) => {
+            if channel.is_empty() {
+                error_line(buffers, history, config, "usage: /join <channel>".to_owned());
+            } else {
+                let channel = normalize_channel_name(&channel);
+                client.join(channel.clone());
+                buffers.switch_to(&channel);
+                history.end();
+                buffers.push_active(
+                    history,
+                    config,
+                    ChatLine::Local {
+                        sender: "*".to_owned(),
+                        text: format!("joined #{}", channel),
+                    },
+                );
+            }
+        }
+        Command::Ban(user) => {
+            if user.is_empty() {
+                error_line(buffers, history, config, "usage: /ban <user>".to_owned());
+            } else {
+                send_mod_command(client, buffers, history, config, format!(".ban {}", user)).await;
+            }
+        }
+        Command::Timeout(user, secs) => {
+            if user.is_empty() {
+                error_line(buffers, history, config, "usage: /timeout <user> <secs>".to_owned());
+            } else {
+                send_mod_command(
+                    client,
+                    buffers,
+                    history,
+                    config,
+                    format!(".timeout {} {}", user, secs),
+                )
+                .await;
+            }
+        }
+        Command::Mod(user) => {
+            if user.is_empty() {
+                error_line(buffers, history, config, "usage: /mod <user>".to_owned());
+            } else {
+                send_mod_command(client, buffers, history, config, format!(".mod {}", user)).await;
+            }
+        }
+        Command::FilterAdd(pattern) => {
+            if pattern.is_empty() {
+                error_line(buffers, history, config, "usage: /filter add <pattern>".to_owned());
+            } else if filters.add_ignore(&pattern) {
+                buffers.push_active(
+                    history,
+                    config,
+                    ChatLine::Local {
+                        sender: "*".to_owned(),
+                        text: format!("now ignoring messages matching /{}/", pattern),
+                    },
+                );
+            } else {
+                error_line(buffers, history, config, format!("invalid pattern: {}", pattern));
+            }
+        }
+        Command::Unknown(name) => {
+            error_line(buffers, history, config, format!("unknown command: /{}", name));
+        }
+    }
+}
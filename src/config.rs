@@ -20,7 +20,8 @@ struct Flags {
 
 #[derive(Deserialize, Serialize)]
 pub struct Config {
-    pub channel: String,
+    /// Channels to join at startup, each one getting its own buffer.
+    pub channels: Vec<String>,
     pub mod_symbol: String,
     pub mod_symbol_width: usize,
     pub vip_symbol: String,
@@ -30,13 +31,63 @@ pub struct Config {
     pub founder_symbol: String,
     pub founder_symbol_width: usize,
     pub invert_below_brightness: u8,
+    /// Number of messages kept in the scrollback ring buffer. This bounds
+    /// how far back a user can page up, not just how many are shown on
+    /// screen at once.
     pub messages_buffer_size: usize,
+    /// Twitch account to log in as. When this and `oauth_token` are both
+    /// set the client authenticates instead of joining anonymously, which
+    /// is required to send messages.
+    pub username: Option<String>,
+    /// OAuth token for `username`, e.g. generated at
+    /// https://twitchapps.com/tmi/. Can also be supplied via the
+    /// `TWITCH_OAUTH_TOKEN` environment variable instead of the config file.
+    pub oauth_token: Option<String>,
+    /// Regexes matched against a message's sender or text; matches are
+    /// dropped before ever entering a buffer.
+    pub ignore: Vec<String>,
+    /// Regexes matched against a message's sender or text that, on a
+    /// match, get a distinct background so they stand out (e.g. your own
+    /// name).
+    pub highlight: Vec<HighlightRule>,
+    pub bridge: BridgeConfig,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HighlightRule {
+    pub pattern: String,
+    pub color: String,
+}
+
+/// Mirrors incoming Twitch chat out to a plain IRC server. Disabled by
+/// default since it requires a server to actually relay to.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BridgeConfig {
+    pub enabled: bool,
+    pub server: String,
+    pub port: u16,
+    pub nick: String,
+    pub channel: String,
+    pub tls: bool,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        BridgeConfig {
+            enabled: false,
+            server: String::new(),
+            port: 6667,
+            nick: "twitch-bridge".to_owned(),
+            channel: "#twitch-bridge".to_owned(),
+            tls: false,
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
-            channel: "stuck_overflow".to_owned(),
+            channels: vec!["stuck_overflow".to_owned()],
             mod_symbol: "🗡 ".to_owned(),
             mod_symbol_width: 2,
             vip_symbol: "💎".to_owned(),
@@ -46,17 +97,26 @@ impl Default for Config {
             founder_symbol: "🥇".to_owned(),
             founder_symbol_width: 2,
             invert_below_brightness: 30,
-            messages_buffer_size: 50,
+            messages_buffer_size: 500,
+            username: None,
+            oauth_token: None,
+            ignore: Vec::new(),
+            highlight: Vec::new(),
+            bridge: BridgeConfig::default(),
         }
     }
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
-        Figment::from(Serialized::defaults(Self::default()))
+        let config: Self = Figment::from(Serialized::defaults(Self::default()))
             .merge(Toml::file(Flags::from_args().config))
             .merge(Env::prefixed("TWITCH_"))
             .extract()
-            .context("failed to load config")
+            .context("failed to load config")?;
+        if config.channels.is_empty() {
+            anyhow::bail!("config error: `channels` must list at least one channel to join");
+        }
+        Ok(config)
     }
 }
@@ -0,0 +1,68 @@
+use crate::config::Config;
+use regex::Regex;
+use tui::style::{Color, Style};
+
+/// Compiled form of `Config.ignore`/`Config.highlight`, built once at
+/// startup (and extended at runtime by `/filter add`) so messages aren't
+/// re-parsing patterns on every render.
+pub struct Filters {
+    ignore: Vec<Regex>,
+    highlight: Vec<(Regex, Style)>,
+}
+
+impl Filters {
+    pub fn compile(config: &Config) -> Self {
+        let ignore = config
+            .ignore
+            .iter()
+            .filter_map(|pattern| Regex::new(pattern).ok())
+            .collect();
+        let highlight = config
+            .highlight
+            .iter()
+            .filter_map(|rule| {
+                Regex::new(&rule.pattern)
+                    .ok()
+                    .map(|re| (re, Style::default().bg(parse_color(&rule.color))))
+            })
+            .collect();
+        Filters { ignore, highlight }
+    }
+
+    /// Adds an ignore rule at runtime (e.g. via `/filter add <pattern>`).
+    /// Returns `false` if `pattern` isn't a valid regex.
+    pub fn add_ignore(&mut self, pattern: &str) -> bool {
+        match Regex::new(pattern) {
+            Ok(re) => {
+                self.ignore.push(re);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn should_ignore(&self, sender: &str, text: &str) -> bool {
+        self.ignore.iter().any(|re| re.is_match(sender) || re.is_match(text))
+    }
+
+    pub fn highlight_style(&self, sender: &str, text: &str) -> Option<Style> {
+        self.highlight
+            .iter()
+            .find(|(re, _)| re.is_match(sender) || re.is_match(text))
+            .map(|(_, style)| *style)
+    }
+}
+
+fn parse_color(name: &str) -> Color {
+    match name.to_lowercase().as_str() {
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "white" => Color::White,
+        _ => Color::Yellow,
+    }
+}
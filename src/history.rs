@@ -0,0 +1,92 @@
+/// Tracks scroll position through the rendered chat log.
+///
+/// `offset` is the index of the topmost visible line in the full,
+/// chronologically ordered (oldest first) list of wrapped lines; `count`
+/// is the total number of wrapped lines currently in the scrollback.
+/// Both are recomputed on every render since they depend on the terminal
+/// size and the wrapped width of each message.
+#[derive(Debug, Default)]
+pub struct History {
+    offset: usize,
+    count: usize,
+    height: usize,
+    width: usize,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// True when the bottom of the scrollback is fully visible, i.e. the
+    /// view is pinned to the latest messages.
+    pub fn at_bottom(&self) -> bool {
+        self.offset + self.height >= self.count
+    }
+
+    fn max_offset(&self) -> usize {
+        self.count.saturating_sub(self.height)
+    }
+
+    pub fn snap_to_bottom(&mut self) {
+        self.offset = self.max_offset();
+    }
+
+    pub fn up(&mut self) {
+        self.offset = self.offset.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.offset = (self.offset + 1).min(self.max_offset());
+    }
+
+    pub fn page_up(&mut self) {
+        self.offset = self.offset.saturating_sub(self.height.max(1));
+    }
+
+    pub fn page_down(&mut self) {
+        self.offset = (self.offset + self.height.max(1)).min(self.max_offset());
+    }
+
+    pub fn home(&mut self) {
+        self.offset = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.snap_to_bottom();
+    }
+
+    /// Recomputes `count` from the actual number of wrapped lines each
+    /// message in the scrollback (oldest first) renders to, and re-clamps
+    /// `offset`, snapping back to the bottom if the view was already
+    /// pinned there. `message_line_counts` must agree with however many
+    /// `ListItem`s the renderer produces per message, or `visible_range`
+    /// will drift out of step with what's actually drawn.
+    pub fn recompute(&mut self, width: usize, height: usize, message_line_counts: impl Iterator<Item = usize>) {
+        let was_at_bottom = self.at_bottom();
+        self.width = width;
+        self.height = height;
+        self.count = message_line_counts.sum();
+        if was_at_bottom {
+            self.snap_to_bottom();
+        } else {
+            self.offset = self.offset.min(self.max_offset());
+        }
+    }
+
+    /// Returns the slice bounds, into a `len`-long list of wrapped lines
+    /// ordered oldest to newest, that are currently visible.
+    pub fn visible_range(&self, len: usize) -> std::ops::Range<usize> {
+        let start = self.offset.min(len);
+        let end = (start + self.height).min(len);
+        start..end
+    }
+}
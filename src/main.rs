@@ -1,31 +1,39 @@
+mod bridge;
+mod buffers;
+mod chat;
+mod commands;
 mod config;
+mod filters;
+mod history;
 
 use anyhow::{Context, Result};
+use bridge::Bridge;
+use buffers::Buffers;
+use chat::{chat_line_wrapped_line_count, render_chat_line, ChatLine};
 use crossterm::event::{self, Event as CEvent, KeyCode};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
-use std::collections::VecDeque;
+use filters::Filters;
+use history::History;
 use std::io;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tui::backend::CrosstermBackend;
-use tui::layout::Corner;
-use tui::style::{Color, Style};
+use tui::layout::{Constraint, Direction, Layout};
+use tui::style::{Modifier, Style};
 use tui::text::{Span, Spans};
-use tui::widgets::{Block, Borders, List, ListItem};
+use tui::widgets::{Block, Borders, List, ListItem, Paragraph};
 use tui::Terminal;
 use twitch_irc::login::StaticLoginCredentials;
-use twitch_irc::message::{PrivmsgMessage, RGBColor, ServerMessage};
+use twitch_irc::message::ServerMessage;
 use twitch_irc::{ClientConfig, TCPTransport, TwitchIRCClient};
+use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug)]
-enum Event {
-    ChatMessage(ServerMessage),
+pub enum Event {
+    ChatMessage(Box<ServerMessage>),
     Input(CEvent),
     Render,
-}
-
-fn luminance(color: &RGBColor) -> f32 {
-    0.2126 * (color.r as f32) + 0.7152 * (color.g as f32) + 0.00722 * (color.b as f32)
+    BridgeStatus(String),
 }
 
 #[tokio::main]
@@ -33,18 +41,26 @@ pub async fn main() -> Result<()> {
     let config = config::Config::load()?;
     let (tx, mut rx) = mpsc::unbounded_channel::<Event>();
 
-    // default configuration is to join chat as anonymous.
-    let irc_config = ClientConfig::default();
+    // Join anonymously unless both a username and an OAuth token are
+    // configured, in which case log in properly so we can send messages.
+    let login_credentials = match (&config.username, &config.oauth_token) {
+        (Some(username), Some(oauth_token)) => {
+            StaticLoginCredentials::new(username.clone(), Some(oauth_token.clone()))
+        }
+        _ => StaticLoginCredentials::anonymous(),
+    };
+    let irc_config = ClientConfig::new_simple(login_credentials);
     let (mut incoming_messages, client) =
         TwitchIRCClient::<TCPTransport, StaticLoginCredentials>::new(irc_config);
 
     let tx2 = tx.clone();
     tokio::spawn(async move {
         while let Some(message) = incoming_messages.recv().await {
-            tx2.send(Event::ChatMessage(message))
+            tx2.send(Event::ChatMessage(Box::new(message)))
                 .expect("sending chat message event");
         }
     });
+    let bridge = Bridge::start(config.bridge.clone(), tx.clone());
     let tick_rate = Duration::from_millis(200);
     tokio::spawn(async move {
         let mut last_tick = Instant::now();
@@ -60,22 +76,25 @@ pub async fn main() -> Result<()> {
                 }
             }
 
-            if last_tick.elapsed() >= tick_rate {
-                if let Ok(_) = tx.send(Event::Render) {
-                    last_tick = Instant::now();
-                }
+            if last_tick.elapsed() >= tick_rate && tx.send(Event::Render).is_ok() {
+                last_tick = Instant::now();
             }
         }
     });
 
-    client.join(config.channel.to_owned());
+    for channel in &config.channels {
+        client.join(buffers::normalize_channel_name(channel));
+    }
 
     enable_raw_mode().context("failed to enable raw mode")?;
     let stdout = io::stdout();
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend).context("can't create terminal backend")?;
     terminal.clear().context("can't clear terminal")?;
-    let mut messages: VecDeque<PrivmsgMessage> = VecDeque::new();
+    let mut buffers = Buffers::new(&config.channels);
+    let mut history = History::new();
+    let mut filters = Filters::compile(&config);
+    let mut input = String::new();
     loop {
         match rx.recv().await.expect("receiving event") {
             Event::Input(event) => {
@@ -87,96 +106,151 @@ pub async fn main() -> Result<()> {
                             terminal.show_cursor().expect("show cursor");
                             std::process::exit(0);
                         }
+                    } else {
+                        match key.code {
+                            KeyCode::PageUp => history.page_up(),
+                            KeyCode::PageDown => history.page_down(),
+                            KeyCode::Up => history.up(),
+                            KeyCode::Down => history.down(),
+                            KeyCode::Home => history.home(),
+                            KeyCode::End => history.end(),
+                            KeyCode::Tab => {
+                                buffers.next();
+                                history.end();
+                            }
+                            KeyCode::BackTab => {
+                                buffers.prev();
+                                history.end();
+                            }
+                            KeyCode::Char(c) => input.push(c),
+                            KeyCode::Backspace => {
+                                input.pop();
+                            }
+                            KeyCode::Enter if !input.is_empty() => {
+                                let text = std::mem::take(&mut input);
+                                if text.starts_with('/') {
+                                    let command = commands::parse(&text);
+                                    commands::dispatch(
+                                        &client,
+                                        &mut buffers,
+                                        &mut history,
+                                        &mut filters,
+                                        &config,
+                                        command,
+                                    )
+                                    .await;
+                                } else {
+                                    let sender = config
+                                        .username
+                                        .clone()
+                                        .unwrap_or_else(|| "you".to_owned());
+                                    buffers.push_active(
+                                        &mut history,
+                                        &config,
+                                        ChatLine::Local {
+                                            sender,
+                                            text: text.clone(),
+                                        },
+                                    );
+                                    let channel = buffers.active_channel().to_owned();
+                                    if let Err(e) = client.say(channel, text).await {
+                                        buffers.push_active(
+                                            &mut history,
+                                            &config,
+                                            ChatLine::Local {
+                                                sender: "error".to_owned(),
+                                                text: format!("failed to send message: {}", e),
+                                            },
+                                        );
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
                     }
                 }
             }
             Event::ChatMessage(message) => {
-                if let ServerMessage::Privmsg(privmsg) = message {
-                    messages.push_front(privmsg);
-                    if messages.len() > config.messages_buffer_size {
-                        messages.pop_back();
+                if let ServerMessage::Privmsg(privmsg) = *message {
+                    if !filters.should_ignore(&privmsg.sender.name, &privmsg.message_text) {
+                        bridge.forward(&privmsg);
+                        let channel = privmsg.channel_login.clone();
+                        buffers.push_incoming(&channel, &mut history, &config, ChatLine::Privmsg(Box::new(privmsg)));
                     }
                 }
             }
+            Event::BridgeStatus(status) => {
+                buffers.push_active(
+                    &mut history,
+                    &config,
+                    ChatLine::Local {
+                        sender: "bridge".to_owned(),
+                        text: status,
+                    },
+                );
+            }
             Event::Render => {
                 terminal
                     .draw(|f| {
-                        let size = f.size();
-                        let mut items: Vec<ListItem> = vec![];
-                        let debug = false;
-                        for m in &messages {
-                            let style = match &m.name_color {
-                                Some(color) => {
-                                    let style =
-                                        Style::default().fg(Color::Rgb(color.r, color.g, color.b));
-                                    if luminance(color) < config.invert_below_brightness as f32 {
-                                        style.bg(Color::Gray)
-                                    } else {
-                                        style
-                                    }
-                                }
-                                None => Style::default(),
-                            };
+                        let chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints(
+                                [Constraint::Length(1), Constraint::Min(1), Constraint::Length(3)].as_ref(),
+                            )
+                            .split(f.size());
+                        let header_area = chunks[0];
+                        let chat_area = chunks[1];
+                        let input_area = chunks[2];
+                        let area_width = chat_area.width as usize;
+                        let area_height = chat_area.height as usize;
 
-                            let is_subscriber = m.badges.iter().any(|b| b.name == "subscriber");
-                            let is_founder = m.badges.iter().any(|b| b.name == "founder");
-                            let is_mod = m.badges.iter().any(|b| b.name == "moderator");
-                            let is_vip = m.badges.iter().any(|b| b.name == "vip");
+                        let messages = buffers.active_messages();
+                        history.recompute(
+                            area_width,
+                            area_height,
+                            messages
+                                .iter()
+                                .rev()
+                                .map(|l| chat_line_wrapped_line_count(&config, l, area_width)),
+                        );
 
-                            let mut width_for_name: usize = m.sender.name.len() + 2 /* ": " */;
-                            let mut badges = String::new();
-                            if is_subscriber {
-                                badges.push_str(&config.subscriber_symbol);
-                                width_for_name += &config.subscriber_symbol_width;
-                            }
-                            if is_founder {
-                                badges.push_str(&config.founder_symbol);
-                                width_for_name += &config.founder_symbol_width;
-                            }
-                            if is_mod {
-                                badges.push_str(&config.mod_symbol);
-                                width_for_name += &config.mod_symbol_width;
-                            }
-                            if is_vip {
-                                badges.push_str(&config.vip_symbol);
-                                width_for_name += &config.vip_symbol_width;
-                            }
-                            let width_for_name = width_for_name;
-                            let width_for_text: usize = size.width as usize - width_for_name;
-                            let lines = textwrap::fill(&m.message_text, width_for_text);
-                            let mut lines = lines.split('\n');
-                            let mut tmpitems: VecDeque<ListItem> = VecDeque::new();
-                            let l = lines.next().expect("message came with no first line");
-                            tmpitems.push_front(ListItem::new(Spans(vec![
-                                Span::raw(badges),
-                                Span::styled(&m.sender.name, style),
-                                Span::raw(": "),
-                                Span::raw(l.to_owned()),
-                            ])));
-                            for l in lines {
-                                tmpitems.push_front(ListItem::new(Spans(vec![
-                                    Span::raw((0..width_for_name).map(|_| " ").collect::<String>()),
-                                    Span::raw(l.to_owned()),
-                                ])));
-                            }
-                            for i in tmpitems {
-                                items.push(i);
-                            }
+                        let mut items: Vec<ListItem> = vec![];
+                        for l in messages.iter().rev() {
+                            items.extend(render_chat_line(&config, &filters, l, area_width));
+                        }
 
-                            if debug {
-                                let i = format!("{:?}", m);
-                                let lines = textwrap::fill(&i, size.width as usize);
-                                let lines = lines.split('\n');
-                                for l in lines.rev() {
-                                    items.push(ListItem::new(Spans(vec![Span::raw(l.to_owned())])));
-                                }
+                        let visible = &items[history.visible_range(items.len())];
+                        let list = List::new(visible.to_vec())
+                            .block(Block::default().borders(Borders::NONE));
+                        f.render_widget(list, chat_area);
+
+                        let mut header_spans = Vec::new();
+                        for (i, name) in buffers.names().iter().enumerate() {
+                            if i > 0 {
+                                header_spans.push(Span::raw(" | "));
                             }
+                            let unread = buffers.unread(name);
+                            let label = if unread > 0 {
+                                format!("{} ({})", name, unread)
+                            } else {
+                                name.clone()
+                            };
+                            let style = if i == buffers.active_index() {
+                                Style::default().add_modifier(Modifier::REVERSED)
+                            } else {
+                                Style::default()
+                            };
+                            header_spans.push(Span::styled(label, style));
                         }
+                        let header = Paragraph::new(Spans(header_spans));
+                        f.render_widget(header, header_area);
 
-                        let list = List::new(items)
-                            .block(Block::default().borders(Borders::NONE))
-                            .start_corner(Corner::BottomLeft);
-                        f.render_widget(list, size);
+                        let input_box = Paragraph::new(input.as_str())
+                            .block(Block::default().borders(Borders::ALL).title("Message"));
+                        f.render_widget(input_box, input_area);
+                        let input_width = input_area.width.saturating_sub(2) as usize;
+                        let cursor_col = UnicodeWidthStr::width(input.as_str()).min(input_width);
+                        f.set_cursor(input_area.x + 1 + cursor_col as u16, input_area.y + 1);
                     })
                     .context("unable to draw on terminal")?;
             }